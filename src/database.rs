@@ -1,85 +1,250 @@
+use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 
 use crate::{Candidate, Contest, District, PartyPreference};
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
 use chrono::{DateTime, Utc};
-use tokio::sync::Mutex;
-use tokio_postgres::{Client, NoTls};
+use log::info;
+use serde::Serialize;
+use tokio_postgres::NoTls;
+
+/// Error returned by any `DbClient` method: either checking a connection out
+/// of the pool failed, or a query against that connection failed.
+#[derive(Debug)]
+pub enum DbError {
+    Pool(bb8::RunError<tokio_postgres::Error>),
+    Postgres(tokio_postgres::Error),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Pool(e) => write!(f, "connection pool error: {}", e),
+            DbError::Postgres(e) => write!(f, "postgres error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+/// A single point in a candidate's vote history, as of one stored update.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPoint {
+    pub timestamp: DateTime<Utc>,
+    pub votes: i32,
+    pub percentage: f64,
+}
+
+/// A candidate's vote/percentage trajectory across every update that
+/// included them, ordered by timestamp.
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateHistory {
+    pub name: String,
+    pub points: Vec<HistoryPoint>,
+}
+
+impl From<bb8::RunError<tokio_postgres::Error>> for DbError {
+    fn from(e: bb8::RunError<tokio_postgres::Error>) -> Self {
+        DbError::Pool(e)
+    }
+}
+
+impl From<tokio_postgres::Error> for DbError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        DbError::Postgres(e)
+    }
+}
 
 pub struct DbClient {
-    client: Mutex<Client>,
+    pool: Pool<PostgresConnectionManager<NoTls>>,
 }
 
-impl DbClient {
-    pub async fn new(connection_string: &str) -> Result<Self, tokio_postgres::Error> {
-        let (client, connection) = tokio_postgres::connect(connection_string, NoTls).await?;
+/// Loads every contest (with its district and candidates) stored under
+/// `update_id`. Shared by every method that needs a full snapshot, so the
+/// contest/district and candidate query logic only lives in one place.
+async fn load_contests_for_update(
+    client: &tokio_postgres::Client,
+    update_id: i32,
+) -> Result<Vec<Contest>, DbError> {
+    let contests = client
+        .query(
+            "SELECT c.id, c.ballot_title,
+                    d.name, d.percent_turnout, d.registered_voters, d.ballots_counted, d.district_type, d.district_type_subheading
+             FROM contests c
+             JOIN districts d ON c.district_id = d.id
+             WHERE c.update_id = $1",
+            &[&update_id],
+        )
+        .await?;
+
+    let mut result = Vec::new();
+
+    for contest_row in contests {
+        let contest_id: i32 = contest_row.get(0);
+        let candidates = client
+            .query(
+                "SELECT name, percentage, votes, party_preference
+                 FROM candidates
+                 WHERE contest_id = $1 AND update_id = $2",
+                &[&contest_id, &update_id],
+            )
+            .await?;
 
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("Connection error: {}", e);
-            }
+        result.push(Contest {
+            id: contest_id as u32,
+            ballot_title: contest_row.get(1),
+            district: District {
+                name: contest_row.get(2),
+                percent_turnout: contest_row.get(3),
+                registered_voters: contest_row.get(4),
+                ballots_counted: contest_row.get(5),
+                district_type: contest_row.get(6),
+                district_type_subheading: contest_row.get(7),
+            },
+            candidates: candidates
+                .into_iter()
+                .map(|c| Candidate {
+                    name: c.get(0),
+                    percentage: c.get(1),
+                    votes: c.get(2),
+                    party_preference: PartyPreference::from_str(&c.get::<_, String>(3))
+                        .unwrap_or(PartyPreference::NotAffiliated),
+                })
+                .collect(),
         });
+    }
 
-        Ok(DbClient {
-            client: Mutex::new(client),
-        })
+    Ok(result)
+}
+
+/// Ordered, append-only list of schema steps. Each step runs exactly once,
+/// in order, the first time `run_migrations` sees its version is unapplied.
+/// Never edit a step once it has shipped — add a new one instead.
+const MIGRATIONS: &[(i32, &str)] = &[
+    (
+        1,
+        "
+        CREATE TABLE IF NOT EXISTS updates (
+            id SERIAL PRIMARY KEY,
+            timestamp TIMESTAMP NOT NULL,
+            total_votes BIGINT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS districts (
+            id SERIAL PRIMARY KEY,
+            name TEXT NOT NULL,
+            percent_turnout FLOAT NOT NULL,
+            registered_voters INTEGER NOT NULL,
+            ballots_counted INTEGER NOT NULL,
+            district_type TEXT NOT NULL,
+            district_type_subheading TEXT NOT NULL,
+            update_id INTEGER NOT NULL REFERENCES updates(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS contests (
+            id INTEGER PRIMARY KEY,
+            ballot_title TEXT NOT NULL,
+            district_id INTEGER NOT NULL REFERENCES districts(id),
+            update_id INTEGER NOT NULL REFERENCES updates(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS candidates (
+            id SERIAL PRIMARY KEY,
+            name TEXT NOT NULL,
+            percentage FLOAT NOT NULL,
+            votes INTEGER NOT NULL,
+            party_preference TEXT NOT NULL,
+            contest_id INTEGER NOT NULL REFERENCES contests(id),
+            update_id INTEGER NOT NULL REFERENCES updates(id)
+        );
+        ",
+    ),
+    (
+        2,
+        "
+        CREATE TABLE IF NOT EXISTS subscriptions (
+            id SERIAL PRIMARY KEY,
+            email TEXT NOT NULL,
+            contest_id INTEGER NOT NULL,
+            UNIQUE (email, contest_id)
+        );
+        ",
+    ),
+    (
+        3,
+        "ALTER TABLE updates ADD COLUMN IF NOT EXISTS error_count INTEGER NOT NULL DEFAULT 0;",
+    ),
+];
+
+impl DbClient {
+    pub async fn new(connection_string: &str) -> Result<Self, DbError> {
+        let manager = PostgresConnectionManager::new_from_stringlike(connection_string, NoTls)
+            .map_err(DbError::Postgres)?;
+        let pool = Pool::builder()
+            .min_idle(Some(1))
+            .max_size(16)
+            .connection_timeout(Duration::from_secs(5))
+            .build(manager)
+            .await?;
+
+        Ok(DbClient { pool })
     }
 
-    pub async fn create_tables(&self) -> Result<(), tokio_postgres::Error> {
-        let client = self.client.lock().await;
+    /// Applies every migration in `MIGRATIONS` newer than the current
+    /// `schema_migrations` high-water mark, each inside its own transaction.
+    pub async fn run_migrations(&self) -> Result<(), DbError> {
+        let mut client = self.pool.get().await?;
         client
             .batch_execute(
-                "
-                CREATE TABLE IF NOT EXISTS updates (
-                    id SERIAL PRIMARY KEY,
-                    timestamp TIMESTAMP NOT NULL,
-                    total_votes BIGINT NOT NULL
-                );
-
-                CREATE TABLE IF NOT EXISTS districts (
-                    id SERIAL PRIMARY KEY,
-                    name TEXT NOT NULL,
-                    percent_turnout FLOAT NOT NULL,
-                    registered_voters INTEGER NOT NULL,
-                    ballots_counted INTEGER NOT NULL,
-                    district_type TEXT NOT NULL,
-                    district_type_subheading TEXT NOT NULL,
-                    update_id INTEGER NOT NULL REFERENCES updates(id)
-                );
-
-                CREATE TABLE IF NOT EXISTS contests (
-                    id INTEGER PRIMARY KEY,
-                    ballot_title TEXT NOT NULL,
-                    district_id INTEGER NOT NULL REFERENCES districts(id),
-                    update_id INTEGER NOT NULL REFERENCES updates(id)
-                );
-
-                CREATE TABLE IF NOT EXISTS candidates (
-                    id SERIAL PRIMARY KEY,
-                    name TEXT NOT NULL,
-                    percentage FLOAT NOT NULL,
-                    votes INTEGER NOT NULL,
-                    party_preference TEXT NOT NULL,
-                    contest_id INTEGER NOT NULL REFERENCES contests(id),
-                    update_id INTEGER NOT NULL REFERENCES updates(id)
-                );
-                ",
+                "CREATE TABLE IF NOT EXISTS schema_migrations (
+                    version INTEGER PRIMARY KEY,
+                    applied_at TIMESTAMP NOT NULL DEFAULT NOW()
+                );",
             )
-            .await
+            .await?;
+
+        let current_version: Option<i32> = client
+            .query_opt("SELECT MAX(version) FROM schema_migrations", &[])
+            .await?
+            .and_then(|row| row.get(0));
+        let current_version = current_version.unwrap_or(0);
+
+        for (version, sql) in MIGRATIONS {
+            if *version <= current_version {
+                continue;
+            }
+
+            let transaction = client.transaction().await?;
+            transaction.batch_execute(sql).await?;
+            transaction
+                .execute(
+                    "INSERT INTO schema_migrations (version) VALUES ($1)",
+                    &[version],
+                )
+                .await?;
+            transaction.commit().await?;
+            info!("Applied schema migration {}", version);
+        }
+
+        Ok(())
     }
 
     pub async fn log_update(
         &self,
         contests: &[Contest],
         total_votes: i64,
-    ) -> Result<(), tokio_postgres::Error> {
-        let mut client = self.client.lock().await;
+        error_count: i32,
+    ) -> Result<(), DbError> {
+        let mut client = self.pool.get().await?;
         let transaction = client.transaction().await?;
 
         // Insert update
         let update_row = transaction
             .query_one(
-                "INSERT INTO updates (timestamp, total_votes) VALUES (NOW(), $1) RETURNING id",
-                &[&total_votes],
+                "INSERT INTO updates (timestamp, total_votes, error_count) VALUES (NOW(), $1, $2) RETURNING id",
+                &[&total_votes, &error_count],
             )
             .await?;
         let update_id: i32 = update_row.get(0);
@@ -87,7 +252,7 @@ impl DbClient {
         // Insert districts, contests, and candidates
         for contest in contests {
             let district_row = transaction.query_one(
-                "INSERT INTO districts (name, percent_turnout, registered_voters, ballots_counted, district_type, district_type_subheading, update_id) 
+                "INSERT INTO districts (name, percent_turnout, registered_voters, ballots_counted, district_type, district_type_subheading, update_id)
                 VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
                 &[&contest.district.name, &contest.district.percent_turnout, &contest.district.registered_voters,
                   &contest.district.ballots_counted, &contest.district.district_type, &contest.district.district_type_subheading, &update_id],
@@ -101,7 +266,7 @@ impl DbClient {
 
             for candidate in &contest.candidates {
                 transaction.execute(
-                    "INSERT INTO candidates (name, percentage, votes, party_preference, contest_id, update_id) 
+                    "INSERT INTO candidates (name, percentage, votes, party_preference, contest_id, update_id)
                     VALUES ($1, $2, $3, $4, $5, $6)",
                     &[&candidate.name, &candidate.percentage, &candidate.votes,
                       &format!("{:?}", candidate.party_preference), &(contest.id as i32), &update_id],
@@ -113,8 +278,8 @@ impl DbClient {
         Ok(())
     }
 
-    pub async fn get_latest_total_votes(&self) -> Result<Option<i64>, tokio_postgres::Error> {
-        let client = self.client.lock().await;
+    pub async fn get_latest_total_votes(&self) -> Result<Option<i64>, DbError> {
+        let client = self.pool.get().await?;
         let row = client
             .query_opt(
                 "SELECT total_votes FROM updates ORDER BY timestamp DESC LIMIT 1",
@@ -125,8 +290,8 @@ impl DbClient {
         Ok(row.map(|r| r.get(0)))
     }
 
-    pub async fn get_latest_data(&self) -> Result<Vec<Contest>, tokio_postgres::Error> {
-        let client = self.client.lock().await;
+    pub async fn get_latest_data(&self) -> Result<Vec<Contest>, DbError> {
+        let client = self.pool.get().await?;
         let latest_update = client
             .query_one(
                 "SELECT id FROM updates ORDER BY timestamp DESC LIMIT 1",
@@ -135,60 +300,138 @@ impl DbClient {
             .await?;
         let update_id: i32 = latest_update.get(0);
 
-        let contests = client
+        load_contests_for_update(&client, update_id).await
+    }
+
+    /// Same as `get_latest_data`, but also returns the `updates.id` the
+    /// snapshot was loaded from, so callers can tag data with its source update.
+    pub async fn get_latest_data_with_update_id(&self) -> Result<(Vec<Contest>, i32), DbError> {
+        let client = self.pool.get().await?;
+        let latest_update = client
+            .query_one(
+                "SELECT id FROM updates ORDER BY timestamp DESC LIMIT 1",
+                &[],
+            )
+            .await?;
+        let update_id: i32 = latest_update.get(0);
+
+        let contests = load_contests_for_update(&client, update_id).await?;
+        Ok((contests, update_id))
+    }
+
+    /// One time-ordered `(timestamp, votes, percentage)` point per update for
+    /// each candidate ever seen in this contest. Candidates are matched across
+    /// snapshots by `(contest_id, name)`, since `candidates.id` is a fresh
+    /// SERIAL every update; a candidate missing from a given update is simply
+    /// absent from its series rather than padded with a zero.
+    pub async fn get_candidate_history(
+        &self,
+        contest_id: u32,
+    ) -> Result<Vec<CandidateHistory>, DbError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT c.name, u.timestamp, c.votes, c.percentage
+                 FROM candidates c
+                 JOIN updates u ON u.id = c.update_id
+                 WHERE c.contest_id = $1
+                 ORDER BY c.name, u.timestamp",
+                &[&(contest_id as i32)],
+            )
+            .await?;
+
+        let mut by_name: std::collections::BTreeMap<String, Vec<HistoryPoint>> =
+            std::collections::BTreeMap::new();
+
+        for row in rows {
+            let name: String = row.get(0);
+            let timestamp: DateTime<Utc> = row.get(1);
+            let votes: i32 = row.get(2);
+            let percentage: f64 = row.get(3);
+
+            by_name
+                .entry(name)
+                .or_insert_with(Vec::new)
+                .push(HistoryPoint {
+                    timestamp,
+                    votes,
+                    percentage,
+                });
+        }
+
+        Ok(by_name
+            .into_iter()
+            .map(|(name, points)| CandidateHistory { name, points })
+            .collect())
+    }
+
+    /// The two most recent `updates` rows (newest first) with their full
+    /// contest/candidate snapshots, for computing "change since last report"
+    /// deltas. Returns fewer than two entries if the table doesn't have them yet.
+    pub async fn get_latest_two_updates(&self) -> Result<Vec<(DateTime<Utc>, Vec<Contest>)>, DbError> {
+        let client = self.pool.get().await?;
+        let update_rows = client
             .query(
-                "SELECT c.id, c.ballot_title, 
-                        d.name, d.percent_turnout, d.registered_voters, d.ballots_counted, d.district_type, d.district_type_subheading
-                 FROM contests c
-                 JOIN districts d ON c.district_id = d.id
-                 WHERE c.update_id = $1",
-                &[&update_id],
+                "SELECT id, timestamp FROM updates ORDER BY timestamp DESC LIMIT 2",
+                &[],
             )
             .await?;
 
         let mut result = Vec::new();
 
-        for contest_row in contests {
-            let contest_id: i32 = contest_row.get(0);
-            let candidates = client
-                .query(
-                    "SELECT name, percentage, votes, party_preference 
-                     FROM candidates 
-                     WHERE contest_id = $1 AND update_id = $2",
-                    &[&contest_id, &update_id],
-                )
-                .await?;
+        for update_row in update_rows {
+            let update_id: i32 = update_row.get(0);
+            let timestamp: DateTime<Utc> = update_row.get(1);
 
-            let contest = Contest {
-                id: contest_id as u32,
-                ballot_title: contest_row.get(1),
-                district: District {
-                    name: contest_row.get(2),
-                    percent_turnout: contest_row.get(3),
-                    registered_voters: contest_row.get(4),
-                    ballots_counted: contest_row.get(5),
-                    district_type: contest_row.get(6),
-                    district_type_subheading: contest_row.get(7),
-                },
-                candidates: candidates
-                    .into_iter()
-                    .map(|c| Candidate {
-                        name: c.get(0),
-                        percentage: c.get(1),
-                        votes: c.get(2),
-                        party_preference: PartyPreference::from_str(&c.get::<_, String>(3))
-                            .unwrap_or(PartyPreference::NotAffiliated),
-                    })
-                    .collect(),
-            };
-
-            result.push(contest);
+            let contests_for_update = load_contests_for_update(&client, update_id).await?;
+            result.push((timestamp, contests_for_update));
         }
 
         Ok(result)
     }
 
-    // pub async fn get_update_timestamps(&self) -> Result<Vec<NaiveDateTime>, tokio_postgres::Error> {
+    /// Subscribes `email` to digest emails for `contest_id`. A duplicate
+    /// subscription is a no-op.
+    pub async fn add_subscription(&self, email: &str, contest_id: u32) -> Result<(), DbError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO subscriptions (email, contest_id) VALUES ($1, $2)
+                 ON CONFLICT (email, contest_id) DO NOTHING",
+                &[&email, &(contest_id as i32)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// All distinct contest IDs with at least one subscriber.
+    pub async fn list_subscribed_contest_ids(&self) -> Result<Vec<u32>, DbError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query("SELECT DISTINCT contest_id FROM subscriptions", &[])
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| row.get::<_, i32>(0) as u32)
+            .collect())
+    }
+
+    /// Every subscriber email following a given contest.
+    pub async fn list_subscriptions_for_contest(
+        &self,
+        contest_id: u32,
+    ) -> Result<Vec<String>, DbError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT email FROM subscriptions WHERE contest_id = $1",
+                &[&(contest_id as i32)],
+            )
+            .await?;
+        Ok(rows.into_iter().map(|row| row.get(0)).collect())
+    }
+
+    // pub async fn get_update_timestamps(&self) -> Result<Vec<NaiveDateTime>, DbError> {
     //     let rows = self.client
     //         .query("SELECT timestamp FROM updates ORDER BY timestamp DESC", &[])
     //         .await?;
@@ -199,65 +442,13 @@ impl DbClient {
     pub async fn get_data_at_timestamp(
         &self,
         timestamp: DateTime<Utc>,
-    ) -> Result<Vec<Contest>, tokio_postgres::Error> {
-        let client = self.client.lock().await;
+    ) -> Result<Vec<Contest>, DbError> {
+        let client = self.pool.get().await?;
         let update_row = client
             .query_one("SELECT id FROM updates WHERE timestamp = $1", &[&timestamp])
             .await?;
         let update_id: i32 = update_row.get(0);
 
-        // Use the same query logic as get_latest_data, but with the specific update_id
-        // This code is similar to get_latest_data, consider refactoring to avoid duplication
-        let contests = client
-            .query(
-                "SELECT c.id, c.ballot_title, 
-                        d.name, d.percent_turnout, d.registered_voters, d.ballots_counted, d.district_type, d.district_type_subheading
-                 FROM contests c
-                 JOIN districts d ON c.district_id = d.id
-                 WHERE c.update_id = $1",
-                &[&update_id],
-            )
-            .await?;
-
-        let mut result = Vec::new();
-
-        for contest_row in contests {
-            let contest_id: i32 = contest_row.get(0);
-            let candidates = client
-                .query(
-                    "SELECT name, percentage, votes, party_preference 
-                     FROM candidates 
-                     WHERE contest_id = $1 AND update_id = $2",
-                    &[&contest_id, &update_id],
-                )
-                .await?;
-
-            let contest = Contest {
-                id: contest_id as u32,
-                ballot_title: contest_row.get(1),
-                district: District {
-                    name: contest_row.get(2),
-                    percent_turnout: contest_row.get(3),
-                    registered_voters: contest_row.get(4),
-                    ballots_counted: contest_row.get(5),
-                    district_type: contest_row.get(6),
-                    district_type_subheading: contest_row.get(7),
-                },
-                candidates: candidates
-                    .into_iter()
-                    .map(|c| Candidate {
-                        name: c.get(0),
-                        percentage: c.get(1),
-                        votes: c.get(2),
-                        party_preference: PartyPreference::from_str(&c.get::<_, String>(3))
-                            .unwrap_or(PartyPreference::NotAffiliated),
-                    })
-                    .collect(),
-            };
-
-            result.push(contest);
-        }
-
-        Ok(result)
+        load_contests_for_update(&client, update_id).await
     }
 }
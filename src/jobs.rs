@@ -0,0 +1,103 @@
+use std::env;
+use std::sync::Arc;
+
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use log::{error, info};
+use tokio::time::{interval, Duration};
+
+use crate::database::DbClient;
+use crate::templates;
+
+/// Polls for a new results snapshot and, when one lands, emails every
+/// contest's subscribers a rendered digest of that contest's current state.
+pub async fn run_email_digest_job(db: Arc<DbClient>) {
+    let mut interval = interval(Duration::from_secs(1800));
+    let mut last_total_votes: Option<i64> = None;
+
+    loop {
+        interval.tick().await;
+
+        let total_votes = match db.get_latest_total_votes().await {
+            Ok(votes) => votes,
+            Err(e) => {
+                error!("Email digest job failed to read total votes: {}", e);
+                continue;
+            }
+        };
+
+        if total_votes.is_none() || total_votes == last_total_votes {
+            continue;
+        }
+        last_total_votes = total_votes;
+
+        if let Err(e) = send_digests(&db).await {
+            error!("Email digest job failed to send digests: {}", e);
+        }
+    }
+}
+
+async fn send_digests(db: &DbClient) -> Result<(), Box<dyn std::error::Error>> {
+    let subscribed_contest_ids = db.list_subscribed_contest_ids().await?;
+    if subscribed_contest_ids.is_empty() {
+        return Ok(());
+    }
+
+    let all_contests = db.get_latest_data().await?;
+
+    for contest_id in subscribed_contest_ids {
+        let subscribers = db.list_subscriptions_for_contest(contest_id).await?;
+        if subscribers.is_empty() {
+            continue;
+        }
+
+        let Some(contest) = all_contests.iter().find(|c| c.id == contest_id) else {
+            continue;
+        };
+
+        let body = templates::contest_details_page(contest.clone(), &[], None).into_string();
+
+        for email in &subscribers {
+            if let Err(e) = send_email(email, &contest.ballot_title, &body) {
+                error!("Failed to email digest to {}: {}", email, e);
+            }
+        }
+
+        info!(
+            "Sent digest for contest {} to {} subscriber(s)",
+            contest_id,
+            subscribers.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Reads an env var, naming it in the error so a missing SMTP setting is
+/// identifiable in the digest job's logs rather than just "not found".
+fn required_env(name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    env::var(name).map_err(|_| format!("{} must be set", name).into())
+}
+
+fn send_email(to: &str, contest_title: &str, html_body: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let smtp_host = required_env("SMTP_HOST")?;
+    let smtp_username = required_env("SMTP_USERNAME")?;
+    let smtp_password = required_env("SMTP_PASSWORD")?;
+    let smtp_from = required_env("SMTP_FROM")?;
+
+    let email = Message::builder()
+        .from(smtp_from.parse()?)
+        .to(to.parse()?)
+        .subject(format!("Election update: {}", contest_title))
+        .header(ContentType::TEXT_HTML)
+        .body(html_body.to_string())?;
+
+    let credentials = Credentials::new(smtp_username, smtp_password);
+    let mailer = SmtpTransport::relay(&smtp_host)?
+        .credentials(credentials)
+        .build();
+
+    mailer.send(&email)?;
+    Ok(())
+}
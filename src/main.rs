@@ -1,17 +1,26 @@
 use actix_cors::Cors;
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use chrono::{DateTime, Utc};
 use env_logger;
+use futures_util::StreamExt;
 use log::{error, info};
 use maud::{html, Markup, Render};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 use std::env;
-use std::{io::Cursor, str::FromStr, sync::Arc};
+use std::{io::Cursor, str::FromStr, sync::Arc, sync::RwLock};
+use tokio::sync::broadcast;
 use tokio::time::{interval, Duration};
+use tokio_stream::wrappers::BroadcastStream;
 
 mod database;
+mod jobs;
+mod search;
+mod tabulation;
 mod templates;
 
+use search::SearchIndex;
+
 use database::DbClient;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -52,7 +61,7 @@ impl Render for PartyPreference {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Candidate {
     name: String,
     percentage: f64,
@@ -60,7 +69,7 @@ struct Candidate {
     party_preference: PartyPreference,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct District {
     name: String,
     percent_turnout: f64,
@@ -70,7 +79,7 @@ struct District {
     district_type_subheading: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Contest {
     ballot_title: String,
     district: District,
@@ -81,6 +90,275 @@ struct Contest {
 #[derive(Clone)]
 struct AppState {
     db: Arc<DbClient>,
+    live_tx: broadcast::Sender<Arc<LiveFrame>>,
+    stream_tx: broadcast::Sender<Arc<StreamSnapshot>>,
+    search_index: Arc<RwLock<SearchIndex>>,
+}
+
+/// The full contest list and total vote count as of one `update_data` run,
+/// broadcast on `AppState::stream_tx` to every `/stream` subscriber.
+#[derive(Debug, Clone, Serialize)]
+struct StreamSnapshot {
+    contests: Vec<Contest>,
+    total_votes: i64,
+}
+
+/// A candidate's vote count/percentage as of a particular update, only emitted
+/// when it changed since the previously broadcast snapshot.
+#[derive(Debug, Clone, Serialize)]
+struct CandidateUpdate {
+    name: String,
+    votes: i32,
+    percentage: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ContestUpdate {
+    id: u32,
+    candidates: Vec<CandidateUpdate>,
+}
+
+/// A diff broadcast over `/live`, tagged with the `updates.id` it was computed
+/// against so a late-joining client can tell it missed frames and resync.
+#[derive(Debug, Clone, Serialize)]
+struct LiveFrame {
+    update_id: i32,
+    contests: Vec<ContestUpdate>,
+}
+
+/// Diffs `current` against `previous`, keeping only candidates whose votes or
+/// percentage changed. Contests with no changed candidates are dropped.
+fn diff_contests(previous: &[Contest], current: &[Contest]) -> Vec<ContestUpdate> {
+    let mut previous_candidates: HashMap<(u32, &str), &Candidate> = HashMap::new();
+    for contest in previous {
+        for candidate in &contest.candidates {
+            previous_candidates.insert((contest.id, candidate.name.as_str()), candidate);
+        }
+    }
+
+    current
+        .iter()
+        .filter_map(|contest| {
+            let changed: Vec<CandidateUpdate> = contest
+                .candidates
+                .iter()
+                .filter(|candidate| {
+                    match previous_candidates.get(&(contest.id, candidate.name.as_str())) {
+                        Some(prev) => {
+                            prev.votes != candidate.votes || prev.percentage != candidate.percentage
+                        }
+                        None => true,
+                    }
+                })
+                .map(|candidate| CandidateUpdate {
+                    name: candidate.name.clone(),
+                    votes: candidate.votes,
+                    percentage: candidate.percentage,
+                })
+                .collect();
+
+            if changed.is_empty() {
+                None
+            } else {
+                Some(ContestUpdate {
+                    id: contest.id,
+                    candidates: changed,
+                })
+            }
+        })
+        .collect()
+}
+
+/// A candidate's change in votes/percentage between the two most recent
+/// updates. `is_new` means the candidate wasn't present in the earlier one.
+#[derive(Debug, Clone)]
+struct CandidateDelta {
+    name: String,
+    vote_delta: i32,
+    percentage_delta: f64,
+    is_new: bool,
+}
+
+/// "Change since last report" for one contest, plus the timestamps of the
+/// two updates being compared so the page can show the gap between them.
+#[derive(Debug, Clone)]
+struct ContestDelta {
+    previous_timestamp: DateTime<Utc>,
+    latest_timestamp: DateTime<Utc>,
+    candidates: Vec<CandidateDelta>,
+}
+
+/// Builds a `ContestDelta` for `contest_id` from the two most recent update
+/// snapshots (newest first), matching candidates by `(contest_id, name)`.
+/// Returns `None` if there isn't yet a prior update to compare against.
+fn compute_contest_delta(
+    contest_id: u32,
+    updates: &[(DateTime<Utc>, Vec<Contest>)],
+) -> Option<ContestDelta> {
+    let (latest_timestamp, latest_contests) = updates.first()?;
+    let (previous_timestamp, previous_contests) = updates.get(1)?;
+
+    let latest_contest = latest_contests.iter().find(|c| c.id == contest_id)?;
+    let previous_candidates: HashMap<&str, &Candidate> = previous_contests
+        .iter()
+        .find(|c| c.id == contest_id)
+        .map(|c| {
+            c.candidates
+                .iter()
+                .map(|candidate| (candidate.name.as_str(), candidate))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let candidates = latest_contest
+        .candidates
+        .iter()
+        .map(
+            |candidate| match previous_candidates.get(candidate.name.as_str()) {
+                Some(previous) => CandidateDelta {
+                    name: candidate.name.clone(),
+                    vote_delta: candidate.votes - previous.votes,
+                    percentage_delta: candidate.percentage - previous.percentage,
+                    is_new: false,
+                },
+                None => CandidateDelta {
+                    name: candidate.name.clone(),
+                    vote_delta: candidate.votes,
+                    percentage_delta: candidate.percentage,
+                    is_new: true,
+                },
+            },
+        )
+        .collect();
+
+    Some(ContestDelta {
+        previous_timestamp: *previous_timestamp,
+        latest_timestamp: *latest_timestamp,
+        candidates,
+    })
+}
+
+/// Periodically checks for a new snapshot and broadcasts a diff against the
+/// last one to any subscribed `/live` clients.
+async fn run_live_poller(db_client: Arc<DbClient>, live_tx: broadcast::Sender<Arc<LiveFrame>>) {
+    let mut interval = interval(Duration::from_secs(10));
+    let mut last_total_votes: Option<i64> = None;
+    let mut last_contests: Vec<Contest> = Vec::new();
+
+    loop {
+        interval.tick().await;
+
+        let total_votes = match db_client.get_latest_total_votes().await {
+            Ok(votes) => votes,
+            Err(e) => {
+                error!("Live poller failed to read total votes: {}", e);
+                continue;
+            }
+        };
+
+        if total_votes.is_none() || total_votes == last_total_votes {
+            continue;
+        }
+
+        let (contests, update_id) = match db_client.get_latest_data_with_update_id().await {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Live poller failed to load latest data: {}", e);
+                continue;
+            }
+        };
+
+        let changed = diff_contests(&last_contests, &contests);
+        last_total_votes = total_votes;
+        last_contests = contests;
+
+        if changed.is_empty() {
+            continue;
+        }
+
+        let _ = live_tx.send(Arc::new(LiveFrame {
+            update_id,
+            contests: changed,
+        }));
+    }
+}
+
+/// SSE endpoint that emits a full `StreamSnapshot` every time `update_data`
+/// writes a new one: an initial event with the current state on connect,
+/// the live feed after that, and periodic keep-alive comments so
+/// intermediaries don't time out the connection.
+async fn stream_updates(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.clone();
+    let mut receiver = data.stream_tx.subscribe();
+
+    let initial = async_stream::stream! {
+        let frame = match db.get_latest_data_with_update_id().await {
+            Ok((contests, _)) => {
+                let total_votes: i64 = contests
+                    .iter()
+                    .flat_map(|c| &c.candidates)
+                    .map(|c| c.votes as i64)
+                    .sum();
+                Some(StreamSnapshot { contests, total_votes })
+            }
+            Err(e) => {
+                error!("Failed to load initial /stream snapshot: {}", e);
+                None
+            }
+        };
+
+        if let Some(frame) = frame {
+            if let Ok(payload) = serde_json::to_string(&frame) {
+                yield Ok::<_, actix_web::Error>(web::Bytes::from(format!("data: {}\n\n", payload)));
+            }
+        }
+
+        let mut keep_alive = interval(Duration::from_secs(15));
+        keep_alive.tick().await; // first tick fires immediately; skip it
+
+        loop {
+            tokio::select! {
+                frame = receiver.recv() => {
+                    match frame {
+                        Ok(frame) => {
+                            if let Ok(payload) = serde_json::to_string(&*frame) {
+                                yield Ok(web::Bytes::from(format!("data: {}\n\n", payload)));
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    }
+                }
+                _ = keep_alive.tick() => {
+                    yield Ok(web::Bytes::from(": keep-alive\n\n"));
+                }
+            }
+        }
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .streaming(initial)
+}
+
+async fn live_updates(data: web::Data<AppState>) -> impl Responder {
+    let receiver = data.live_tx.subscribe();
+    let stream = BroadcastStream::new(receiver).filter_map(|frame| async move {
+        match frame {
+            Ok(frame) => {
+                let payload = serde_json::to_string(&*frame).ok()?;
+                Some(Ok::<_, actix_web::Error>(web::Bytes::from(format!(
+                    "data: {}\n\n",
+                    payload
+                ))))
+            }
+            Err(_) => None,
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .streaming(stream)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -153,17 +431,50 @@ struct ElectionData {
     percent_of_votes: QuotedFloat,
 }
 
-async fn fetch_and_parse_csv() -> Result<(Vec<Contest>, i64), Box<dyn std::error::Error>> {
+/// A CSV row that failed to deserialize into `ElectionData`, kept around so a
+/// single malformed row degrades that row's data rather than the whole refresh.
+#[derive(Debug, Clone)]
+struct RowError {
+    row_number: usize,
+    raw_record: String,
+    error: String,
+}
+
+async fn fetch_and_parse_csv(
+) -> Result<(Vec<Contest>, i64, Vec<RowError>), Box<dyn std::error::Error>> {
     let csv_url: String = env::var("CSV_URL").expect("No CSV URL provided.");
     let response = reqwest::get(csv_url).await?.text().await?;
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(true)
         .from_reader(Cursor::new(response));
-    let mut parsed_data: Vec<ElectionData> = Vec::new();
+    let headers = reader.headers()?.clone();
 
-    for result in reader.deserialize() {
-        let record: ElectionData = result?;
-        parsed_data.push(record);
+    let mut parsed_data: Vec<ElectionData> = Vec::new();
+    let mut row_errors: Vec<RowError> = Vec::new();
+
+    for (row_number, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                // The row itself didn't parse as CSV (e.g. a column count
+                // mismatch against the header), so there's no StringRecord to
+                // report alongside the error.
+                row_errors.push(RowError {
+                    row_number,
+                    raw_record: String::new(),
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        match record.deserialize::<ElectionData>(Some(&headers)) {
+            Ok(data) => parsed_data.push(data),
+            Err(e) => row_errors.push(RowError {
+                row_number,
+                raw_record: record.iter().collect::<Vec<_>>().join(","),
+                error: e.to_string(),
+            }),
+        }
     }
 
     let contests = process_election_data(parsed_data);
@@ -173,18 +484,43 @@ async fn fetch_and_parse_csv() -> Result<(Vec<Contest>, i64), Box<dyn std::error
         .map(|c| c.votes as i64)
         .sum();
 
-    Ok((contests, total_votes))
+    Ok((contests, total_votes, row_errors))
 }
 
-async fn update_data(db_client: &DbClient) -> Result<(), Box<dyn std::error::Error>> {
-    let (parsed_data, total_votes) = fetch_and_parse_csv().await?;
+async fn update_data(
+    db_client: &DbClient,
+    stream_tx: &broadcast::Sender<Arc<StreamSnapshot>>,
+    search_index: &Arc<RwLock<SearchIndex>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (parsed_data, total_votes, row_errors) = fetch_and_parse_csv().await?;
+
+    if !row_errors.is_empty() {
+        error!(
+            "Skipped {} malformed CSV row(s) during refresh: {}",
+            row_errors.len(),
+            row_errors
+                .iter()
+                .map(|e| format!("row {} [{}]: {}", e.row_number, e.raw_record, e.error))
+                .collect::<Vec<_>>()
+                .join("; ")
+        );
+    }
 
     let latest_total_votes = db_client.get_latest_total_votes().await?;
 
     if latest_total_votes.map_or(true, |votes| votes != total_votes) {
         // Log the update to PostgreSQL
-        db_client.log_update(&parsed_data, total_votes).await?;
+        db_client
+            .log_update(&parsed_data, total_votes, row_errors.len() as i32)
+            .await?;
         info!("Data updated. New total votes: {}", total_votes);
+
+        *search_index.write().unwrap() = SearchIndex::build(&parsed_data);
+
+        let _ = stream_tx.send(Arc::new(StreamSnapshot {
+            contests: parsed_data,
+            total_votes,
+        }));
     } else {
         info!("No change in data. Current total votes: {}", total_votes);
     }
@@ -246,11 +582,28 @@ async fn get_all_data(db_client: &DbClient) -> Result<Vec<Contest>, actix_web::E
     })
 }
 
-async fn index(data: web::Data<AppState>) -> impl Responder {
-    match get_all_data(&data.db).await.map(contests_by_ballot_title) {
-        Ok(contests) => HttpResponse::Ok()
-            .content_type("text/html")
-            .body(templates::index(&contests).into_string()),
+/// True when the client's `Accept` header mentions `application/json`, so
+/// routes that serve both a page and a REST representation can pick one.
+/// Quality values are not weighed: any mention of JSON selects it over HTML.
+fn wants_json(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(actix_web::http::header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(|accept| accept.contains("application/json"))
+        .unwrap_or(false)
+}
+
+async fn index(req: HttpRequest, data: web::Data<AppState>) -> impl Responder {
+    match get_all_data(&data.db).await {
+        Ok(contests) => {
+            if wants_json(&req) {
+                HttpResponse::Ok().json(contests)
+            } else {
+                HttpResponse::Ok()
+                    .content_type("text/html")
+                    .body(templates::index(&contests_by_ballot_title(contests)).into_string())
+            }
+        }
         Err(e) => {
             error!("Failed to get ballot titles: {}", e);
             HttpResponse::InternalServerError().body("Failed to load page")
@@ -258,7 +611,11 @@ async fn index(data: web::Data<AppState>) -> impl Responder {
     }
 }
 
-async fn contest_page(data: web::Data<AppState>, path: web::Path<u32>) -> impl Responder {
+async fn contest_page(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<u32>,
+) -> impl Responder {
     let contest_id = path.into_inner();
     match get_all_data(&data.db).await {
         Ok(all_data) => {
@@ -268,12 +625,33 @@ async fn contest_page(data: web::Data<AppState>, path: web::Path<u32>) -> impl R
                 return HttpResponse::Ok().body("No data available for this contest.");
             }
 
+            if wants_json(&req) {
+                return HttpResponse::Ok().json(contest.unwrap());
+            }
+
             contest.as_mut().unwrap().candidates.sort_by(|a, b| {
                 b.percentage
                     .partial_cmp(&a.percentage)
                     .unwrap_or(std::cmp::Ordering::Equal)
             });
-            let markup = templates::contest_details_page(contest.unwrap());
+
+            let history = match data.db.get_candidate_history(contest_id).await {
+                Ok(history) => history,
+                Err(e) => {
+                    error!("Failed to load candidate history: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let delta = match data.db.get_latest_two_updates().await {
+                Ok(updates) => compute_contest_delta(contest_id, &updates),
+                Err(e) => {
+                    error!("Failed to load delta updates: {}", e);
+                    None
+                }
+            };
+
+            let markup = templates::contest_details_page(contest.unwrap(), &history, delta.as_ref());
             HttpResponse::Ok()
                 .content_type("text/html")
                 .body(markup.into_string())
@@ -282,6 +660,159 @@ async fn contest_page(data: web::Data<AppState>, path: web::Path<u32>) -> impl R
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+}
+
+/// Looks up `q` against the in-memory index and returns the matching
+/// contests (title/district matches ranked above candidate-only matches) as
+/// either an HTML partial for a search box or JSON, per `Accept`.
+async fn search(req: HttpRequest, data: web::Data<AppState>, query: web::Query<SearchQuery>) -> impl Responder {
+    let matching_ids = data.search_index.read().unwrap().search(&query.q);
+
+    let all_contests = match get_all_data(&data.db).await {
+        Ok(contests) => contests,
+        Err(e) => {
+            error!("Search failed to load contests: {}", e);
+            return HttpResponse::InternalServerError().body("Search failed");
+        }
+    };
+
+    let mut by_id: HashMap<u32, Contest> =
+        all_contests.into_iter().map(|c| (c.id, c)).collect();
+    let results: Vec<Contest> = matching_ids
+        .into_iter()
+        .filter_map(|id| by_id.remove(&id))
+        .collect();
+
+    if wants_json(&req) {
+        HttpResponse::Ok().json(results)
+    } else {
+        HttpResponse::Ok()
+            .content_type("text/html")
+            .body(templates::search_results(&query.q, &results).into_string())
+    }
+}
+
+async fn api_contests(data: web::Data<AppState>) -> impl Responder {
+    match get_all_data(&data.db).await {
+        Ok(contests) => HttpResponse::Ok().json(contests),
+        Err(e) => {
+            error!("Failed to get contests: {}", e);
+            HttpResponse::InternalServerError().body("Failed to load contests")
+        }
+    }
+}
+
+async fn api_contest_by_id(data: web::Data<AppState>, path: web::Path<u32>) -> impl Responder {
+    let contest_id = path.into_inner();
+    match get_all_data(&data.db).await {
+        Ok(contests) => match contests.into_iter().find(|c| c.id == contest_id) {
+            Some(contest) => HttpResponse::Ok().json(contest),
+            None => HttpResponse::NotFound().body("No contest with that ID"),
+        },
+        Err(e) => {
+            error!("Failed to get contest: {}", e);
+            HttpResponse::InternalServerError().body("Failed to load contest")
+        }
+    }
+}
+
+async fn api_contest_by_title(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let title = path.into_inner();
+    match get_all_data(&data.db).await {
+        Ok(contests) => {
+            let matches: Vec<Contest> = contests
+                .into_iter()
+                .filter(|c| c.ballot_title == title)
+                .collect();
+            HttpResponse::Ok().json(matches)
+        }
+        Err(e) => {
+            error!("Failed to get contests by title: {}", e);
+            HttpResponse::InternalServerError().body("Failed to load contests")
+        }
+    }
+}
+
+/// Per-candidate vote history for a contest, as JSON, for charting late-ballot
+/// swings over the counting period.
+async fn contest_timeline(data: web::Data<AppState>, path: web::Path<u32>) -> impl Responder {
+    let contest_id = path.into_inner();
+    match data.db.get_candidate_history(contest_id).await {
+        Ok(history) => HttpResponse::Ok().json(history),
+        Err(e) => {
+            error!("Failed to load contest timeline: {}", e);
+            HttpResponse::InternalServerError().body("Failed to load timeline")
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TabulateQuery {
+    /// Number of seats to fill; omitted or 1 runs single-winner IRV instead of STV.
+    seats: Option<usize>,
+}
+
+/// Ranked-choice tabulation for a single contest. The request body is a
+/// cast-vote-record CSV (one row per ballot, columns in preference order);
+/// the response is the round-by-round elimination/transfer chart.
+async fn tabulate_contest(
+    path: web::Path<u32>,
+    query: web::Query<TabulateQuery>,
+    body: web::Bytes,
+) -> impl Responder {
+    let contest_id = path.into_inner();
+    let csv_text = match std::str::from_utf8(&body) {
+        Ok(text) => text,
+        Err(_) => return HttpResponse::BadRequest().body("Request body must be UTF-8 CSV"),
+    };
+
+    let ballots = match tabulation::parse_cvr_csv(csv_text) {
+        Ok(ballots) => ballots,
+        Err(e) => {
+            return HttpResponse::BadRequest().body(format!("Failed to parse CVR CSV: {}", e))
+        }
+    };
+
+    let seats = query.seats.unwrap_or(1);
+    let markup = if seats <= 1 {
+        let rounds = tabulation::run_irv(&ballots);
+        templates::irv_results_page(contest_id, &rounds)
+    } else {
+        let rounds = tabulation::run_stv(&ballots, seats);
+        templates::stv_results_page(contest_id, seats, &rounds)
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/html")
+        .body(markup.into_string())
+}
+
+#[derive(Debug, Deserialize)]
+struct SubscribeForm {
+    email: String,
+    contest_id: u32,
+}
+
+async fn subscribe(data: web::Data<AppState>, form: web::Form<SubscribeForm>) -> impl Responder {
+    match data
+        .db
+        .add_subscription(&form.email, form.contest_id)
+        .await
+    {
+        Ok(()) => HttpResponse::Ok().body("Subscribed. You'll get an email when this contest's results change."),
+        Err(e) => {
+            error!("Failed to add subscription: {}", e);
+            HttpResponse::InternalServerError().body("Failed to subscribe")
+        }
+    }
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
@@ -297,24 +828,36 @@ async fn main() -> std::io::Result<()> {
 
     db_client
         .clone()
-        .create_tables()
+        .run_migrations()
         .await
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
 
+    let (live_tx, _) = broadcast::channel(16);
+    let (stream_tx, _) = broadcast::channel(16);
+
+    let initial_contests = db_client.get_latest_data().await.unwrap_or_default();
+    let search_index = Arc::new(RwLock::new(SearchIndex::build(&initial_contests)));
+
     let app_state = web::Data::new(AppState {
         db: db_client.clone(),
+        live_tx: live_tx.clone(),
+        stream_tx: stream_tx.clone(),
+        search_index: search_index.clone(),
     });
 
     tokio::spawn(async move {
         let mut interval = interval(Duration::from_secs(3600)); // Check every hour
         loop {
             interval.tick().await;
-            if let Err(e) = update_data(&db_client).await {
+            if let Err(e) = update_data(&db_client, &stream_tx, &search_index).await {
                 error!("Failed to update data: {}", e);
             }
         }
     });
 
+    tokio::spawn(run_live_poller(app_state.db.clone(), live_tx));
+    tokio::spawn(jobs::run_email_digest_job(app_state.db.clone()));
+
     HttpServer::new(move || {
         // Create a CORS middleware
         let cors = Cors::permissive();
@@ -323,6 +866,15 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors) // Add this line to wrap the entire app with CORS middleware
             .app_data(app_state.clone())
             .route("/", web::get().to(index))
+            .route("/api/contests", web::get().to(api_contests))
+            .route("/api/contests/by-title/{title}", web::get().to(api_contest_by_title))
+            .route("/api/contests/{contest_id}", web::get().to(api_contest_by_id))
+            .route("/search", web::get().to(search))
+            .route("/live", web::get().to(live_updates))
+            .route("/stream", web::get().to(stream_updates))
+            .route("/subscribe", web::post().to(subscribe))
+            .route("/contest/{contest_id}/tabulate", web::post().to(tabulate_contest))
+            .route("/contest/{contest_id}/timeline", web::get().to(contest_timeline))
             .route("/{contest_id}", web::get().to(contest_page))
     })
     .bind("0.0.0.0:8080")?
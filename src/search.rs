@@ -0,0 +1,84 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Contest;
+
+/// An in-memory inverted index over the latest snapshot, rebuilt whenever a
+/// new one is stored. Tokens from `ballot_title`/`district.name` are tracked
+/// separately from candidate-name tokens so contest-title matches can be
+/// ranked above candidate-only matches.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    title_tokens: HashMap<String, HashSet<u32>>,
+    candidate_tokens: HashMap<String, HashSet<u32>>,
+}
+
+/// Lowercases and splits on non-alphanumeric boundaries, so a word tokenizes
+/// the same way whether it came from a ballot title, a district name, a
+/// candidate's name or a search query.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+impl SearchIndex {
+    pub fn build(contests: &[Contest]) -> Self {
+        let mut title_tokens: HashMap<String, HashSet<u32>> = HashMap::new();
+        let mut candidate_tokens: HashMap<String, HashSet<u32>> = HashMap::new();
+
+        for contest in contests {
+            for token in tokenize(&contest.ballot_title).chain(tokenize(&contest.district.name)) {
+                title_tokens.entry(token).or_default().insert(contest.id);
+            }
+            for candidate in &contest.candidates {
+                for token in tokenize(&candidate.name) {
+                    candidate_tokens.entry(token).or_default().insert(contest.id);
+                }
+            }
+        }
+
+        SearchIndex {
+            title_tokens,
+            candidate_tokens,
+        }
+    }
+
+    /// Contest IDs matching any word of `query` by prefix, title/district
+    /// matches first. A multi-word query (e.g. "jane smith") is split the
+    /// same way the index was built and each word is matched independently,
+    /// then unioned, so "King County Sheriff" finds contests via any of its
+    /// three words rather than needing one token to match the whole phrase.
+    pub fn search(&self, query: &str) -> Vec<u32> {
+        let words: Vec<String> = tokenize(query).collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        let title_matches = Self::matching_ids(&self.title_tokens, &words);
+        let mut candidate_matches = Self::matching_ids(&self.candidate_tokens, &words);
+        candidate_matches.retain(|id| !title_matches.contains(id));
+
+        let mut title_matches: Vec<u32> = title_matches.into_iter().collect();
+        let mut candidate_matches: Vec<u32> = candidate_matches.into_iter().collect();
+        title_matches.sort_unstable();
+        candidate_matches.sort_unstable();
+
+        title_matches.extend(candidate_matches);
+        title_matches
+    }
+
+    fn matching_ids(tokens: &HashMap<String, HashSet<u32>>, words: &[String]) -> HashSet<u32> {
+        words
+            .iter()
+            .flat_map(|word| {
+                tokens
+                    .iter()
+                    .filter(|(token, _)| token.starts_with(word.as_str()))
+                    .flat_map(|(_, ids)| ids.iter().copied())
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// A single ranked ballot: candidate names in descending preference order, as
+/// read from one row of a cast-vote-record CSV (its `Ballot Response`
+/// columns, left to right).
+#[derive(Debug, Clone)]
+pub struct Ballot {
+    pub preferences: Vec<String>,
+}
+
+impl Ballot {
+    /// The highest-ranked candidate on this ballot who hasn't been eliminated,
+    /// or `None` if every ranked preference has been eliminated (exhausted).
+    fn current_choice(&self, eliminated: &std::collections::HashSet<String>) -> Option<&str> {
+        self.preferences
+            .iter()
+            .find(|name| !eliminated.contains(*name))
+            .map(|name| name.as_str())
+    }
+}
+
+/// Parses a cast-vote-record CSV into ballots. Every column is treated as a
+/// ranked preference in order; blank cells (a voter skipping a rank) are
+/// dropped rather than kept as empty preferences.
+pub fn parse_cvr_csv(csv_text: &str) -> Result<Vec<Ballot>, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(Cursor::new(csv_text));
+
+    let mut ballots = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let preferences = record
+            .iter()
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+            .collect();
+        ballots.push(Ballot { preferences });
+    }
+
+    Ok(ballots)
+}
+
+/// One round of IRV tabulation: each continuing candidate's tally, who (if
+/// anyone) was eliminated at the end of the round, and how many ballots had
+/// no remaining preference to count (exhausted).
+#[derive(Debug, Clone)]
+pub struct IrvRound {
+    pub tallies: Vec<(String, usize)>,
+    pub eliminated: Option<String>,
+    pub exhausted_ballots: usize,
+}
+
+/// Runs single-winner instant-runoff voting to completion, returning one
+/// `IrvRound` per round. The winner is the first-ranked candidate of the
+/// final round once they hold a majority of continuing ballots, or the sole
+/// remaining candidate if it never crosses 50%.
+pub fn run_irv(ballots: &[Ballot]) -> Vec<IrvRound> {
+    let mut candidates: std::collections::HashSet<String> = ballots
+        .iter()
+        .flat_map(|b| b.preferences.iter().cloned())
+        .collect();
+    let mut eliminated: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut rounds = Vec::new();
+
+    while !candidates.is_empty() {
+        let mut tallies: HashMap<String, usize> = candidates.iter().cloned().map(|c| (c, 0)).collect();
+        let mut exhausted_ballots = 0;
+
+        for ballot in ballots {
+            match ballot.current_choice(&eliminated) {
+                Some(choice) => {
+                    *tallies.get_mut(choice).unwrap() += 1;
+                }
+                None => exhausted_ballots += 1,
+            }
+        }
+
+        let continuing_total: usize = tallies.values().sum();
+        let mut sorted: Vec<(String, usize)> = tallies.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let leader_has_majority = sorted
+            .first()
+            .map(|(_, votes)| continuing_total > 0 && *votes * 2 > continuing_total)
+            .unwrap_or(false);
+
+        if leader_has_majority || candidates.len() == 1 {
+            rounds.push(IrvRound {
+                tallies: sorted,
+                eliminated: None,
+                exhausted_ballots,
+            });
+            break;
+        }
+
+        // Eliminate the lowest-tallied candidate (ties broken alphabetically
+        // for determinism) and transfer their ballots next round.
+        let (loser, _) = sorted.last().unwrap().clone();
+        eliminated.insert(loser.clone());
+        candidates.remove(&loser);
+
+        rounds.push(IrvRound {
+            tallies: sorted,
+            eliminated: Some(loser),
+            exhausted_ballots,
+        });
+    }
+
+    rounds
+}
+
+/// One round of STV tabulation: each continuing candidate's weighted tally,
+/// anyone who crossed the Droop quota and was elected this round (with the
+/// surplus transfer weight applied to their ballots), anyone eliminated, and
+/// the count of ballots exhausted of preferences.
+#[derive(Debug, Clone)]
+pub struct StvRound {
+    pub tallies: Vec<(String, f64)>,
+    pub elected: Vec<String>,
+    pub eliminated: Option<String>,
+    pub exhausted_ballots: f64,
+}
+
+/// Runs multi-seat single transferable vote tabulation for `seats` seats,
+/// using the Droop quota and fractional surplus transfer.
+pub fn run_stv(ballots: &[Ballot], seats: usize) -> Vec<StvRound> {
+    // A fully spoiled ballot (no preferences at all) shouldn't inflate the
+    // quota, since it can never count toward any candidate's tally.
+    let valid_ballots = ballots.iter().filter(|b| !b.preferences.is_empty()).count();
+    let quota = (valid_ballots / (seats + 1)) + 1;
+
+    let mut candidates: std::collections::HashSet<String> = ballots
+        .iter()
+        .flat_map(|b| b.preferences.iter().cloned())
+        .collect();
+    let mut eliminated: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut elected: Vec<String> = Vec::new();
+    // Each ballot's current transfer weight, starting at 1.0 and reduced
+    // fractionally whenever it counts toward an elected candidate's surplus.
+    let mut weights: Vec<f64> = vec![1.0; ballots.len()];
+    let mut rounds = Vec::new();
+
+    while elected.len() < seats && !candidates.is_empty() {
+        let mut tallies: HashMap<String, f64> = candidates.iter().cloned().map(|c| (c, 0.0)).collect();
+        let mut exhausted_ballots = 0.0;
+
+        for (ballot, weight) in ballots.iter().zip(weights.iter()) {
+            match ballot.current_choice(&eliminated) {
+                Some(choice) => {
+                    *tallies.get_mut(choice).unwrap() += weight;
+                }
+                None => exhausted_ballots += weight,
+            }
+        }
+
+        let newly_elected: Vec<String> = tallies
+            .iter()
+            .filter(|(_, votes)| **votes >= quota as f64)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        if !newly_elected.is_empty() {
+            for name in &newly_elected {
+                let total = tallies[name];
+                let surplus = total - quota as f64;
+                let transfer_weight = if total > 0.0 { surplus / total } else { 0.0 };
+
+                for (ballot, weight) in ballots.iter().zip(weights.iter_mut()) {
+                    if ballot.current_choice(&eliminated) == Some(name.as_str()) {
+                        *weight *= transfer_weight;
+                    }
+                }
+
+                candidates.remove(name);
+                eliminated.insert(name.clone());
+                elected.push(name.clone());
+            }
+
+            let mut sorted: Vec<(String, f64)> = tallies.into_iter().collect();
+            sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
+
+            rounds.push(StvRound {
+                tallies: sorted,
+                elected: newly_elected,
+                eliminated: None,
+                exhausted_ballots,
+            });
+            continue;
+        }
+
+        if candidates.len() + elected.len() <= seats {
+            // Everyone left fills the remaining seats without meeting quota.
+            let mut sorted: Vec<(String, f64)> = tallies.into_iter().collect();
+            sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
+            let remaining: Vec<String> = candidates.into_iter().collect();
+            elected.extend(remaining.iter().cloned());
+            rounds.push(StvRound {
+                tallies: sorted,
+                elected: remaining,
+                eliminated: None,
+                exhausted_ballots,
+            });
+            break;
+        }
+
+        let mut sorted: Vec<(String, f64)> = tallies.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
+        let (loser, _) = sorted.last().unwrap().clone();
+        eliminated.insert(loser.clone());
+        candidates.remove(&loser);
+
+        rounds.push(StvRound {
+            tallies: sorted,
+            elected: Vec::new(),
+            eliminated: Some(loser),
+            exhausted_ballots,
+        });
+    }
+
+    rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballot(preferences: &[&str]) -> Ballot {
+        Ballot {
+            preferences: preferences.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn tally_of<'a>(tallies: &'a [(String, usize)], name: &str) -> Option<&'a usize> {
+        tallies.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn irv_clean_majority_winner() {
+        let ballots = vec![
+            ballot(&["A"]),
+            ballot(&["A"]),
+            ballot(&["A"]),
+            ballot(&["B"]),
+            ballot(&["C"]),
+        ];
+
+        let rounds = run_irv(&ballots);
+
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].eliminated, None);
+        assert_eq!(tally_of(&rounds[0].tallies, "A"), Some(&3));
+    }
+
+    #[test]
+    fn irv_elimination_transfers_to_winner() {
+        let ballots = vec![
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["B", "A"]),
+            ballot(&["C", "A"]),
+            ballot(&["C", "A"]),
+        ];
+
+        let rounds = run_irv(&ballots);
+
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].eliminated, Some("B".to_string()));
+        assert_eq!(rounds[1].eliminated, None);
+        // B's ballot transfers to A, giving A a 3/5 majority in round 2.
+        assert_eq!(tally_of(&rounds[1].tallies, "A"), Some(&3));
+    }
+
+    #[test]
+    fn stv_quota_met_on_first_round() {
+        let ballots = vec![
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["B", "A"]),
+            ballot(&["C"]),
+            ballot(&["C"]),
+            ballot(&["D"]),
+            ballot(&["D"]),
+        ];
+
+        // Droop quota for 10 ballots / 2 seats = floor(10/3) + 1 = 4.
+        let rounds = run_stv(&ballots, 2);
+
+        assert_eq!(rounds[0].elected, vec!["A".to_string()]);
+        assert_eq!(
+            rounds[0]
+                .tallies
+                .iter()
+                .find(|(n, _)| n == "A")
+                .map(|(_, v)| *v),
+            Some(5.0)
+        );
+    }
+
+    #[test]
+    fn stv_surplus_transfers_to_next_preference() {
+        let ballots = vec![
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["A", "B"]),
+            ballot(&["B", "A"]),
+            ballot(&["C"]),
+            ballot(&["C"]),
+            ballot(&["D"]),
+            ballot(&["D"]),
+        ];
+
+        let rounds = run_stv(&ballots, 2);
+
+        // A's 5 first-choice ballots are worth (5 - quota) / 5 = 0.2 each once
+        // A is elected, so B's round-2 tally is 1.0 from its own first-choice
+        // ballot plus 5 * 0.2 = 1.0 transferred from A, for a total of 2.0 --
+        // more than the 1.0 it would have without the surplus transfer.
+        let round_two = &rounds[1];
+        assert_eq!(
+            round_two
+                .tallies
+                .iter()
+                .find(|(n, _)| n == "B")
+                .map(|(_, v)| *v),
+            Some(2.0)
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::{collections::HashMap, env};
 
-use crate::Contest;
+use crate::database::CandidateHistory;
+use crate::tabulation::{IrvRound, StvRound};
+use crate::{CandidateDelta, Contest, ContestDelta};
+use chrono::{DateTime, Utc};
 use maud::{html, Markup, DOCTYPE};
 
 pub fn header() -> Markup {
@@ -17,11 +20,35 @@ pub fn header() -> Markup {
                 @if goatcounter_url.is_ok() {
                     script data-goatcounter=(goatcounter_url.unwrap()) src="//gc.zgo.at/count.js" async {}
                 }
+                script { (maud::PreEscaped(LIVE_UPDATES_SCRIPT)) }
             }
         }
     }
 }
 
+/// Listens on `/live` and swaps vote counts/percentages in place on any page
+/// that renders candidates with `data-contest-id`/`data-candidate` markers.
+const LIVE_UPDATES_SCRIPT: &str = r#"
+document.addEventListener('DOMContentLoaded', () => {
+    const source = new EventSource('/live');
+    source.onmessage = (event) => {
+        const frame = JSON.parse(event.data);
+        for (const contest of frame.contests) {
+            for (const candidate of contest.candidates) {
+                const row = document.querySelector(
+                    `[data-contest-id="${contest.id}"][data-candidate="${candidate.name}"]`
+                );
+                if (!row) continue;
+                const votes = row.querySelector('[data-field="votes"]');
+                const percentage = row.querySelector('[data-field="percentage"]');
+                if (votes) votes.textContent = candidate.votes.toLocaleString();
+                if (percentage) percentage.textContent = candidate.percentage.toFixed(2) + '%';
+            }
+        }
+    };
+});
+"#;
+
 pub fn footer() -> Markup {
     html!(
         footer class="container mx-auto my-4" {
@@ -56,6 +83,9 @@ pub fn index(ballot_info: &HashMap<String, Vec<Contest>>) -> Markup {
     keys_sorted.sort_unstable();
     html! {
         (layout(html!(
+                form class="mb-4" method="get" action="/search" {
+                    input class="border rounded px-2 py-1 w-full max-w-md" type="search" name="q" placeholder="Search contests and candidates\u{2026}" required;
+                }
                 h2 class="text-2xl font-semibold mb-2" { "Contests by Ballot Title" }
                 div class="grid md:grid-cols-2 gap-4" {
                 @for title in keys_sorted {
@@ -73,7 +103,150 @@ pub fn index(ballot_info: &HashMap<String, Vec<Contest>>) -> Markup {
     }
 }
 
-pub fn contest_details_page(contest: Contest) -> Markup {
+/// Renders a candidate's vote-count trajectory as an inline SVG sparkline.
+/// A single point (or none) renders as a flat placeholder line, since there's
+/// nothing yet to trace a trend through.
+fn sparkline(history: &CandidateHistory) -> Markup {
+    const WIDTH: f64 = 160.0;
+    const HEIGHT: f64 = 36.0;
+
+    let max_votes = history
+        .points
+        .iter()
+        .map(|p| p.votes)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    let n = history.points.len();
+    let path = if n < 2 {
+        format!("M0,{h} L{w},{h}", h = HEIGHT, w = WIDTH)
+    } else {
+        history
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let x = (i as f64 / (n - 1) as f64) * WIDTH;
+                let y = HEIGHT - (point.votes as f64 / max_votes) * HEIGHT;
+                format!("{}{:.1},{:.1}", if i == 0 { "M" } else { " L" }, x, y)
+            })
+            .collect()
+    };
+
+    html! {
+        svg class="inline-block align-middle" width=(WIDTH) height=(HEIGHT) viewBox=(format!("0 0 {} {}", WIDTH, HEIGHT)) {
+            path d=(path) fill="none" stroke="currentColor" stroke-width="1.5" {}
+        }
+    }
+}
+
+/// Renders a signed "+1204 votes, +0.3 pts" badge, or "new" for a candidate
+/// who wasn't present in the prior snapshot.
+fn delta_badge(delta: &CandidateDelta) -> Markup {
+    html! {
+        @if delta.is_new {
+            span class="text-xs text-gray-500 ml-1" { "new" }
+        } @else {
+            span class=(if delta.vote_delta > 0 { "text-xs text-green-700 ml-1" } else if delta.vote_delta < 0 { "text-xs text-red-700 ml-1" } else { "text-xs text-gray-500 ml-1" }) {
+                (format!("{:+}", delta.vote_delta)) " votes, " (format!("{:+.1}", delta.percentage_delta)) " pts"
+            }
+        }
+    }
+}
+
+/// Renders the human-readable gap between the two updates a delta spans,
+/// e.g. "34 min" or "2h 10m", so the delta is interpretable.
+fn format_gap(delta: &ContestDelta) -> String {
+    let minutes = (delta.latest_timestamp - delta.previous_timestamp).num_minutes();
+    if minutes < 60 {
+        format!("{} min", minutes)
+    } else {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Fixed rotation of stroke colors for `timeline_chart`'s candidate lines,
+/// reused in order rather than generated so the legend and lines always agree.
+const TIMELINE_COLORS: &[&str] = &[
+    "#1d4ed8", "#b91c1c", "#15803d", "#a16207", "#6d28d9", "#0e7490",
+];
+
+/// Renders every candidate's vote trajectory as one overlaid multi-line SVG
+/// chart (of the same data the `/contest/{id}/timeline` API returns), with a color-keyed
+/// legend below it. Candidates are placed on a shared time axis built from
+/// the union of timestamps across all series, so a candidate missing from an
+/// update just leaves a gap in their line rather than shifting the axis.
+fn timeline_chart(history: &[CandidateHistory]) -> Markup {
+    const WIDTH: f64 = 600.0;
+    const HEIGHT: f64 = 220.0;
+
+    let mut timestamps: Vec<DateTime<Utc>> = history
+        .iter()
+        .flat_map(|h| h.points.iter().map(|p| p.timestamp))
+        .collect();
+    timestamps.sort_unstable();
+    timestamps.dedup();
+
+    let n = timestamps.len();
+    let max_votes = history
+        .iter()
+        .flat_map(|h| h.points.iter().map(|p| p.votes))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    html! {
+        div class="bg-white rounded shadow p-2 mt-2" {
+            h4 class="text-lg font-semibold mb-2" { "Vote trajectory" }
+            svg class="w-full" viewBox=(format!("0 0 {} {}", WIDTH, HEIGHT)) preserveAspectRatio="none" {
+                @for (i, candidate_history) in history.iter().enumerate() {
+                    @let path = if n < 2 || candidate_history.points.len() < 2 {
+                        String::new()
+                    } else {
+                        let mut segments = String::new();
+                        let mut previous_index: Option<usize> = None;
+                        for point in &candidate_history.points {
+                            let x_index = timestamps.iter().position(|t| *t == point.timestamp).unwrap_or(0);
+                            let x = (x_index as f64 / (n - 1) as f64) * WIDTH;
+                            let y = HEIGHT - (point.votes as f64 / max_votes) * HEIGHT;
+                            // A skipped timestamp starts a new subpath, leaving a gap.
+                            let command = if previous_index.map_or(false, |p| p + 1 == x_index) { " L" } else { " M" };
+                            segments.push_str(&format!("{}{:.1},{:.1}", command, x, y));
+                            previous_index = Some(x_index);
+                        }
+                        segments
+                    };
+                    @if !path.is_empty() {
+                        path d=(path.trim_start()) fill="none" stroke=(TIMELINE_COLORS[i % TIMELINE_COLORS.len()]) stroke-width="2" {}
+                    }
+                }
+            }
+            ul class="flex flex-wrap gap-x-3 gap-y-1 text-xs mt-1" {
+                @for (i, candidate_history) in history.iter().enumerate() {
+                    li {
+                        span style=(format!("color:{}", TIMELINE_COLORS[i % TIMELINE_COLORS.len()])) { "\u{25cf} " }
+                        (candidate_history.name)
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn contest_details_page(
+    contest: Contest,
+    history: &[CandidateHistory],
+    delta: Option<&ContestDelta>,
+) -> Markup {
+    let history_by_name: HashMap<&str, &CandidateHistory> = history
+        .iter()
+        .map(|h| (h.name.as_str(), h))
+        .collect();
+    let delta_by_name: HashMap<&str, &CandidateDelta> = delta
+        .map(|d| d.candidates.iter().map(|c| (c.name.as_str(), c)).collect())
+        .unwrap_or_default();
+
     html! {
         (layout(html! (
             h2 class="text-2xl font-semibold mb-2" { (contest.ballot_title) }
@@ -85,14 +258,131 @@ pub fn contest_details_page(contest: Contest) -> Markup {
             // p { strong { "Registered Voters: " } (contest.registered_voters_for_district) }
             // p { strong { "Turnout: " } (format!("{:.2}%", contest.percent_turnout_for_district.0)) }
             div class="bg-white rounded shadow p-2 mt-2" {
-                h4 class="text-lg font-semibold mb-2" { "Results:" }
-                ul class="inline-grid grid-cols-2 gap-x-1 gap-y-2" {
+                h4 class="text-lg font-semibold mb-2" {
+                    "Results:"
+                    @if let Some(delta) = delta {
+                        span class="text-sm font-normal text-gray-500" { " (change over last " (format_gap(delta)) ")" }
+                    }
+                }
+                ul class="inline-grid grid-cols-3 gap-x-1 gap-y-2" {
                     @for candidate in contest.candidates {
-                        li class="contents" {
+                        li class="contents" data-contest-id=(contest.id) data-candidate=(candidate.name) {
                             div {(candidate.name) " ("
                             (candidate.party_preference)
                             "):"}
-                            div { (candidate.votes) " votes (" (format!("{:.2}%", candidate.percentage)) ")"}
+                            div {
+                                span data-field="votes" { (candidate.votes) } " votes (" span data-field="percentage" { (format!("{:.2}%", candidate.percentage)) } ")"
+                                @if let Some(candidate_delta) = delta_by_name.get(candidate.name.as_str()) {
+                                    (delta_badge(candidate_delta))
+                                }
+                            }
+                            div {
+                                @if let Some(candidate_history) = history_by_name.get(candidate.name.as_str()) {
+                                    (sparkline(candidate_history))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            @if !history.is_empty() {
+                (timeline_chart(history))
+            }
+            form class="mt-2 flex gap-2 items-center" method="post" action="/subscribe" {
+                input type="hidden" name="contest_id" value=(contest.id);
+                input class="border rounded px-2 py-1 text-sm" type="email" name="email" placeholder="you@example.com" required;
+                button class="bg-blue-900 text-white rounded px-2 py-1 text-sm" type="submit" { "Email me updates" }
+            }
+        )))
+    }
+}
+
+/// Renders the elimination/transfer chart for a completed IRV tabulation:
+/// one table per round, continuing candidates sorted by tally.
+pub fn irv_results_page(contest_id: u32, rounds: &[IrvRound]) -> Markup {
+    html! {
+        (layout(html! (
+            h2 class="text-2xl font-semibold mb-2" { "Ranked-choice results: contest " (contest_id) }
+            @for (i, round) in rounds.iter().enumerate() {
+                div class="bg-white rounded shadow p-2 mt-2" {
+                    h4 class="text-lg font-semibold mb-2" { "Round " (i + 1) }
+                    table class="w-full text-left" {
+                        thead { tr { th { "Candidate" } th { "Votes" } } }
+                        tbody {
+                            @for (name, votes) in &round.tallies {
+                                tr class=(if round.eliminated.as_deref() == Some(name.as_str()) { "line-through text-gray-400" } else { "" }) {
+                                    td { (name) }
+                                    td { (votes) }
+                                }
+                            }
+                        }
+                    }
+                    p class="text-sm text-gray-500 mt-1" {
+                        @if let Some(eliminated) = &round.eliminated {
+                            "Eliminated: " (eliminated) " \u{2014} "
+                        }
+                        "Exhausted ballots: " (round.exhausted_ballots)
+                    }
+                }
+            }
+        )))
+    }
+}
+
+/// Renders the elimination/transfer chart for a completed STV tabulation:
+/// one table per round, with winners highlighted.
+pub fn stv_results_page(contest_id: u32, seats: usize, rounds: &[StvRound]) -> Markup {
+    html! {
+        (layout(html! (
+            h2 class="text-2xl font-semibold mb-2" { "Ranked-choice results: contest " (contest_id) " (" (seats) " seats)" }
+            @for (i, round) in rounds.iter().enumerate() {
+                div class="bg-white rounded shadow p-2 mt-2" {
+                    h4 class="text-lg font-semibold mb-2" { "Round " (i + 1) }
+                    table class="w-full text-left" {
+                        thead { tr { th { "Candidate" } th { "Votes" } } }
+                        tbody {
+                            @for (name, votes) in &round.tallies {
+                                tr class=(
+                                    if round.elected.contains(name) { "font-bold text-green-700" }
+                                    else if round.eliminated.as_deref() == Some(name.as_str()) { "line-through text-gray-400" }
+                                    else { "" }
+                                ) {
+                                    td { (name) }
+                                    td { (format!("{:.2}", votes)) }
+                                }
+                            }
+                        }
+                    }
+                    p class="text-sm text-gray-500 mt-1" {
+                        @if !round.elected.is_empty() {
+                            "Elected: " (round.elected.join(", ")) " \u{2014} "
+                        }
+                        @if let Some(eliminated) = &round.eliminated {
+                            "Eliminated: " (eliminated) " \u{2014} "
+                        }
+                        "Exhausted ballots: " (format!("{:.2}", round.exhausted_ballots))
+                    }
+                }
+            }
+        )))
+    }
+}
+
+/// Renders `/search` results: contests ranked by the search index, already
+/// in title-match-first order.
+pub fn search_results(query: &str, results: &[Contest]) -> Markup {
+    html! {
+        (layout(html! (
+            h2 class="text-2xl font-semibold mb-2" { "Search results for \u{201c}" (query) "\u{201d}" }
+            @if results.is_empty() {
+                p { "No contests or candidates matched." }
+            } @else {
+                ul class="grid gap-2" {
+                    @for contest in results {
+                        li class="bg-white rounded shadow p-2" {
+                            a class="underline hover:text-slate-900" href=(format!("/{}", contest.id)) {
+                                (contest.ballot_title) " \u{2014} " (contest.district.name)
+                            }
                         }
                     }
                 }